@@ -0,0 +1,132 @@
+// Optional GeoIP-based per-country categorization. Resolves each
+// config's host to an IP (caching lookups so the same host isn't
+// resolved twice), looks up the ISO country code in a local MaxMind
+// .mmdb database, and buckets configs by country - prepending a flag
+// and the code to each config's remark so clients display location.
+// Degrades to a no-op when no database path is given or the file
+// can't be opened.
+
+use crate::parser::VlessConfig;
+use maxminddb::geoip2;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+pub struct GeoResolver {
+    reader: Option<maxminddb::Reader<Vec<u8>>>,
+    dns_cache: HashMap<String, Option<IpAddr>>,
+}
+
+impl GeoResolver {
+    // Opens the mmdb at `path` if given. Any failure (no flag, missing
+    // file, bad format) degrades to a resolver that skips geo
+    // bucketing entirely rather than erroring out.
+    pub fn open(path: Option<&str>) -> Self {
+        let reader = path.and_then(|path| match maxminddb::Reader::open_readfile(path) {
+            Ok(reader) => {
+                println!("🌍 GeoIP database loaded from {}", path);
+                Some(reader)
+            }
+            Err(e) => {
+                eprintln!(
+                    "⚠ Could not open GeoIP database at {}: {} (skipping geo bucketing)",
+                    path, e
+                );
+                None
+            }
+        });
+
+        GeoResolver {
+            reader,
+            dns_cache: HashMap::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.reader.is_some()
+    }
+
+    async fn resolve(&mut self, host: &str) -> Option<IpAddr> {
+        if let Some(cached) = self.dns_cache.get(host) {
+            return *cached;
+        }
+
+        let resolved = match host.parse::<IpAddr>() {
+            Ok(ip) => Some(ip),
+            Err(_) => tokio::net::lookup_host((host, 0))
+                .await
+                .ok()
+                .and_then(|mut addrs| addrs.next())
+                .map(|addr| addr.ip()),
+        };
+
+        self.dns_cache.insert(host.to_string(), resolved);
+        resolved
+    }
+
+    fn country_code(&self, ip: IpAddr) -> Option<String> {
+        let reader = self.reader.as_ref()?;
+        let country: geoip2::Country = reader.lookup(ip).ok()?;
+        country.country?.iso_code.map(str::to_string)
+    }
+
+    async fn lookup(&mut self, address: &str) -> Option<String> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let ip = self.resolve(address).await?;
+        self.country_code(ip)
+    }
+}
+
+// ISO country code -> regional-indicator flag emoji (e.g. "US" -> 🇺🇸).
+fn country_flag(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .filter_map(|c| char::from_u32(0x1F1E6 + (c.to_ascii_uppercase() as u32 - 'A' as u32)))
+        .collect()
+}
+
+// Rebuild a vless:// line with its remark prefixed by a flag + country code.
+fn with_country_remark(raw: &str, remark: &str, code: &str) -> String {
+    let without_fragment = raw.split('#').next().unwrap_or(raw);
+    let new_remark = if remark.is_empty() {
+        format!("{} {}", country_flag(code), code)
+    } else {
+        format!("{} {} {}", country_flag(code), code, remark)
+    };
+    let encoded =
+        percent_encoding::utf8_percent_encode(&new_remark, percent_encoding::NON_ALPHANUMERIC);
+    format!("{}#{}", without_fragment, encoded)
+}
+
+// Parse each config, resolve its host to a country, and bucket by ISO
+// country code. Returns an empty map (no-op) when GeoIP is disabled;
+// configs that can't be resolved/looked up are simply skipped.
+pub async fn bucket_by_country(
+    resolver: &mut GeoResolver,
+    configs: &[String],
+) -> HashMap<String, Vec<String>> {
+    let mut buckets: HashMap<String, Vec<String>> = HashMap::new();
+    if !resolver.is_enabled() {
+        return buckets;
+    }
+
+    println!("\n🌍 Categorizing by country...");
+
+    for line in configs {
+        let Some(parsed) = VlessConfig::parse(line) else {
+            continue;
+        };
+        let Some(code) = resolver.lookup(&parsed.address).await else {
+            continue;
+        };
+        let tagged = with_country_remark(&parsed.raw, &parsed.remark, &code);
+        buckets.entry(code).or_default().push(tagged);
+    }
+
+    for (code, configs) in &buckets {
+        println!("   {}: {} configs", code, configs.len());
+    }
+
+    buckets
+}
@@ -1,42 +1,62 @@
 // Ultra-fast VLESS config processor in Rust
 // Optimized for maximum throughput and minimal memory usage
 
+use base64::Engine;
+use rand::Rng;
 use rayon::prelude::*;
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
 use tokio::task;
 
-// Transport types as enum for fastest matching
-#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
-enum TransportType {
-    WebSocket,
-    Grpc,
-    Tcp,
-    Tls,
-    XHttp,
+mod geoip;
+mod output;
+mod parser;
+mod probe;
+use parser::{SecurityMode, TransportType, VlessConfig};
+use probe::ProbeResult;
+
+// Per-host and global caps on in-flight downloads, to stay polite with
+// providers that host many subscription links behind the same domain.
+const PER_HOST_CONCURRENCY: usize = 4;
+const GLOBAL_CONCURRENCY: usize = 16;
+
+// Retry tuning: exponential backoff starting at 1s, doubling each attempt.
+const MAX_RETRIES: u32 = 3;
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+
+// Custom error type that is Send + Sync, split into retryable and fatal
+// variants so the retry loop knows when backing off is worth it.
+#[derive(Debug)]
+enum FetchError {
+    Timeout,
+    Connection(String),
+    HttpStatus(u16),
+    Decode(String),
 }
 
-impl TransportType {
-    fn as_str(&self) -> &'static str {
+impl FetchError {
+    fn is_retryable(&self) -> bool {
         match self {
-            Self::WebSocket => "ws",
-            Self::Grpc => "grpc",
-            Self::Tcp => "tcp",
-            Self::Tls => "tls",
-            Self::XHttp => "xhttp",
+            FetchError::Timeout | FetchError::Connection(_) => true,
+            FetchError::HttpStatus(code) => *code == 429 || *code >= 500,
+            FetchError::Decode(_) => false,
         }
     }
 }
 
-// Custom error type that is Send + Sync
-#[derive(Debug)]
-struct FetchError(String);
-
 impl std::fmt::Display for FetchError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        match self {
+            FetchError::Timeout => write!(f, "request timed out"),
+            FetchError::Connection(msg) => write!(f, "connection error: {}", msg),
+            FetchError::HttpStatus(code) => write!(f, "unexpected status: {}", code),
+            FetchError::Decode(msg) => write!(f, "decode error: {}", msg),
+        }
     }
 }
 
@@ -61,7 +81,7 @@ fn decode_base64(input: &str) -> String {
             break;
         }
         
-        match base64::decode(result.trim()) {
+        match base64::engine::general_purpose::STANDARD.decode(result.trim()) {
             Ok(decoded) => {
                 match String::from_utf8(decoded) {
                     Ok(s) if s != result => result = s,
@@ -75,86 +95,121 @@ fn decode_base64(input: &str) -> String {
     result
 }
 
-// Extract transport type from config
-#[inline(always)]
-fn extract_transport(config: &str) -> TransportType {
-    let query = match config.find('?') {
-        Some(pos) => &config[pos..],
-        None => return TransportType::Tcp,
-    };
-    
-    if let Some(type_pos) = query.find("type=") {
-        let type_start = type_pos + 5;
-        let type_end = query[type_start..]
-            .find('&')
-            .map(|p| type_start + p)
-            .unwrap_or(query.len());
-        
-        let transport = &query[type_start..type_end];
-        
-        return match transport {
-            t if t.starts_with("ws") => TransportType::WebSocket,
-            t if t.starts_with("grpc") => TransportType::Grpc,
-            t if t.starts_with("xhttp") || t.starts_with("httpupgrade") => TransportType::XHttp,
-            _ => TransportType::Tcp,
-        };
+// Pull the host out of an http(s) URL using a fast substring approach,
+// so hosts can be grouped for per-host concurrency limits without
+// depending on the vless:// parser (subscription URLs aren't vless://).
+fn extract_host(url: &str) -> &str {
+    let after_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = after_scheme.split(['/', '?', '#']).next().unwrap_or("");
+    let host_port = authority.rsplit('@').next().unwrap_or(authority);
+    match host_port.rsplit_once(':') {
+        Some((host, port)) if port.bytes().all(|b| b.is_ascii_digit()) => host,
+        _ => host_port,
     }
-    
-    if query.contains("security=tls") {
-        return TransportType::Tls;
-    }
-    
-    TransportType::Tcp
 }
 
-// Async download with timeout
-async fn fetch_url(url: String) -> Result<Vec<String>, FetchError> {
-    println!("📥 Downloading: {}", url);
-    
+// Single download attempt - no retry logic, that lives in fetch_with_retry.
+async fn fetch_url(url: &str) -> Result<Vec<String>, FetchError> {
     let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(15))
+        .timeout(Duration::from_secs(15))
         .build()
-        .map_err(|e| FetchError(format!("Client build error: {}", e)))?;
-    
-    let response = client.get(&url)
+        .map_err(|e| FetchError::Connection(e.to_string()))?;
+
+    let response = client.get(url)
         .header("User-Agent", "Mozilla/5.0")
         .send()
         .await
-        .map_err(|e| FetchError(format!("Request error: {}", e)))?;
-    
+        .map_err(|e| {
+            if e.is_timeout() {
+                FetchError::Timeout
+            } else {
+                FetchError::Connection(e.to_string())
+            }
+        })?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(FetchError::HttpStatus(status.as_u16()));
+    }
+
     let body = response.text().await
-        .map_err(|e| FetchError(format!("Body read error: {}", e)))?;
-    
+        .map_err(|e| FetchError::Decode(e.to_string()))?;
+
     let decoded = decode_base64(&body);
-    
+
     let configs: Vec<String> = decoded
         .lines()
         .filter(|line| line.starts_with("vless://"))
         .map(|s| s.to_string())
         .collect();
-    
+
     println!("   ✓ Found {} VLESS configs", configs.len());
     Ok(configs)
 }
 
-// Download all URLs concurrently
+// Retry wrapper with exponential backoff (1s, 2s, 4s, ...) plus jitter,
+// so transient timeouts/5xx/429 don't lose configs outright.
+async fn fetch_with_retry(url: &str) -> Result<Vec<String>, FetchError> {
+    let mut attempt = 0;
+    loop {
+        println!("📥 Downloading ({}/{}): {}", attempt + 1, MAX_RETRIES + 1, url);
+        match fetch_url(url).await {
+            Ok(configs) => return Ok(configs),
+            Err(err) if attempt < MAX_RETRIES && err.is_retryable() => {
+                let backoff = BASE_BACKOFF * 2u32.pow(attempt);
+                let jitter = Duration::from_millis(rand::thread_rng().gen_range(0..250));
+                println!("   ⚠ {} (retrying in {:?})", err, backoff + jitter);
+                tokio::time::sleep(backoff + jitter).await;
+                attempt += 1;
+            }
+            Err(err) => {
+                println!("   ✗ {} giving up on: {}", err, url);
+                return Err(err);
+            }
+        }
+    }
+}
+
+// Download all URLs concurrently, grouping by host so no single provider
+// gets hammered with more than PER_HOST_CONCURRENCY requests at once,
+// with a GLOBAL_CONCURRENCY cap across all hosts combined.
 async fn fetch_all(urls: Vec<String>) -> Vec<String> {
     println!("\n{}", "=".repeat(60));
     println!("📥 Fetching configs from all URLs...");
     println!("{}", "=".repeat(60));
-    
+
+    let global_sem = Arc::new(Semaphore::new(GLOBAL_CONCURRENCY));
+    let mut host_sems: HashMap<String, Arc<Semaphore>> = HashMap::new();
+    for url in &urls {
+        host_sems
+            .entry(extract_host(url).to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(PER_HOST_CONCURRENCY)));
+    }
+
     let tasks: Vec<_> = urls
         .into_iter()
-        .map(|url| task::spawn(fetch_url(url)))
+        .map(|url| {
+            let global_sem = Arc::clone(&global_sem);
+            let host_sem = Arc::clone(&host_sems[extract_host(&url)]);
+            task::spawn(async move {
+                // Host permit first: a host with more queued URLs than
+                // PER_HOST_CONCURRENCY must not hold a scarce global
+                // permit while still waiting for a host slot, or it
+                // starves downloads to every other host.
+                let _host_permit = host_sem.acquire_owned().await.unwrap();
+                let _global_permit = global_sem.acquire_owned().await.unwrap();
+                fetch_with_retry(&url).await
+            })
+        })
         .collect();
-    
+
     let mut all_configs = Vec::new();
     for task in tasks {
         if let Ok(Ok(configs)) = task.await {
             all_configs.extend(configs);
         }
     }
-    
+
     println!("\n✓ Total downloaded: {}", all_configs.len());
     all_configs
 }
@@ -174,37 +229,64 @@ fn deduplicate(configs: Vec<String>) -> Vec<String> {
     unique_vec
 }
 
-// Categorize configs using parallel processing
-fn categorize(configs: Vec<String>) -> HashMap<TransportType, Vec<String>> {
-    println!("\n📊 Categorizing by transport type...");
-    
-    let categorized: HashMap<TransportType, Vec<String>> = configs
+type CategorizedConfigs = (
+    HashMap<TransportType, Vec<String>>,
+    HashMap<SecurityMode, Vec<String>>,
+    usize,
+);
+
+// Categorize configs using parallel processing. Parses each line with
+// the structured vless:// parser and buckets it by both transport and
+// security mode; malformed lines (rejected by the parser) are dropped
+// and counted instead of silently passing through.
+fn categorize(configs: Vec<String>) -> CategorizedConfigs {
+    println!("\n📊 Categorizing by transport and security...");
+
+    let (by_transport, by_security, malformed): CategorizedConfigs = configs
         .par_iter()
         .fold(
-            || HashMap::new(),
-            |mut map, config| {
-                let transport = extract_transport(config);
-                map.entry(transport)
-                    .or_insert_with(Vec::new)
-                    .push(config.clone());
-                map
-            }
+            || (HashMap::new(), HashMap::new(), 0usize),
+            |(mut by_transport, mut by_security, mut malformed), config| {
+                match VlessConfig::parse(config) {
+                    Some(parsed) => {
+                        by_transport
+                            .entry(parsed.transport)
+                            .or_insert_with(Vec::new)
+                            .push(config.clone());
+                        by_security
+                            .entry(parsed.security)
+                            .or_insert_with(Vec::new)
+                            .push(config.clone());
+                    }
+                    None => malformed += 1,
+                }
+                (by_transport, by_security, malformed)
+            },
         )
         .reduce(
-            || HashMap::new(),
-            |mut a, b| {
-                for (k, mut v) in b {
-                    a.entry(k).or_insert_with(Vec::new).append(&mut v);
+            || (HashMap::new(), HashMap::new(), 0usize),
+            |(mut a_t, mut a_s, a_m), (b_t, b_s, b_m)| {
+                for (k, mut v) in b_t {
+                    a_t.entry(k).or_insert_with(Vec::new).append(&mut v);
                 }
-                a
-            }
+                for (k, mut v) in b_s {
+                    a_s.entry(k).or_insert_with(Vec::new).append(&mut v);
+                }
+                (a_t, a_s, a_m + b_m)
+            },
         );
-    
-    for (transport, configs) in &categorized {
+
+    for (transport, configs) in &by_transport {
         println!("   {}: {} configs", transport.as_str().to_uppercase(), configs.len());
     }
-    
-    categorized
+    for (security, configs) in &by_security {
+        println!("   security={}: {} configs", security.as_str(), configs.len());
+    }
+    if malformed > 0 {
+        println!("   ⚠ skipped {} malformed configs", malformed);
+    }
+
+    (by_transport, by_security, malformed)
 }
 
 // Save all configs to file
@@ -220,6 +302,19 @@ fn save_all_configs(configs: &[String], output_dir: &Path) -> std::io::Result<()
     Ok(())
 }
 
+// Save alive configs, fastest-first
+fn save_alive_configs(alive: &[ProbeResult], output_dir: &Path) -> std::io::Result<()> {
+    let filepath = output_dir.join("vless_alive.txt");
+    let mut file = File::create(&filepath)?;
+
+    for result in alive {
+        writeln!(file, "{}", result.config)?;
+    }
+
+    println!("\n✓ Saved {} alive configs to: vless_alive.txt", alive.len());
+    Ok(())
+}
+
 // Save categorized configs
 fn save_by_transport(
     categories: &HashMap<TransportType, Vec<String>>,
@@ -238,7 +333,54 @@ fn save_by_transport(
         
         println!("   ✓ {} ({} configs)", filename, configs.len());
     }
-    
+
+    Ok(())
+}
+
+// Save configs bucketed by security mode (vless_sec_tls.txt, vless_sec_reality.txt, ...)
+fn save_by_security(
+    categories: &HashMap<SecurityMode, Vec<String>>,
+    output_dir: &Path,
+) -> std::io::Result<()> {
+    println!("\n💾 Saving configs by security mode...");
+
+    for (security, configs) in categories {
+        // Prefixed to avoid colliding with save_by_transport's filenames -
+        // TransportType::Tls and SecurityMode::Tls both render as "tls".
+        let filename = format!("vless_sec_{}.txt", security.as_str());
+        let filepath = output_dir.join(&filename);
+        let mut file = File::create(&filepath)?;
+
+        for config in configs {
+            writeln!(file, "{}", config)?;
+        }
+
+        println!("   ✓ {} ({} configs)", filename, configs.len());
+    }
+
+    Ok(())
+}
+
+// Save configs bucketed by GeoIP country code (vless_US.txt, vless_DE.txt, ...)
+fn save_by_country(categories: &HashMap<String, Vec<String>>, output_dir: &Path) -> std::io::Result<()> {
+    if categories.is_empty() {
+        return Ok(());
+    }
+
+    println!("\n💾 Saving configs by country...");
+
+    for (code, configs) in categories {
+        let filename = format!("vless_{}.txt", code);
+        let filepath = output_dir.join(&filename);
+        let mut file = File::create(&filepath)?;
+
+        for config in configs {
+            writeln!(file, "{}", config)?;
+        }
+
+        println!("   ✓ {} ({} configs)", filename, configs.len());
+    }
+
     Ok(())
 }
 
@@ -268,7 +410,12 @@ fn update_readme(output_dir: &Path, repo_url: &str) -> std::io::Result<()> {
     
     let mut files: Vec<_> = fs::read_dir(output_dir)?
         .filter_map(Result::ok)
-        .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("txt"))
+        .filter(|e| {
+            matches!(
+                e.path().extension().and_then(|s| s.to_str()),
+                Some("txt") | Some("json") | Some("yaml")
+            )
+        })
         .map(|e| e.file_name().to_string_lossy().to_string())
         .collect();
     
@@ -293,7 +440,8 @@ fn update_readme(output_dir: &Path, repo_url: &str) -> std::io::Result<()> {
     
     readme.push_str(
         "\n## 🔗 How to Use\n\n\
-         Copy any raw link above and add it as a subscription in your V2Ray client.\n\n\
+         Copy any raw link above and add it as a subscription in your V2Ray client, \
+         or import `sing-box.json` / `clash.yaml` directly into sing-box or Clash.\n\n\
          ### Example:\n\
          ```\n\
          https://raw.githubusercontent.com/YOUR_USERNAME/YOUR_REPO/main/configs/vless_config_1.txt\n\
@@ -318,49 +466,208 @@ fn update_readme(output_dir: &Path, repo_url: &str) -> std::io::Result<()> {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: {} <subscriptions_file>", args[0]);
-        std::process::exit(1);
+    let raw_args: Vec<String> = std::env::args().collect();
+    let cli = match parse_args(&raw_args) {
+        Ok(cli) => cli,
+        Err(msg) => {
+            eprintln!("Error: {}", msg);
+            eprintln!(
+                "Usage: {} <subscriptions_file> [--daemon] [--interval <hours>] [--mmdb <path>]",
+                raw_args[0]
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let output_dir = Path::new("configs");
+    fs::create_dir_all(output_dir)?;
+    let repo_url = "https://github.com/Matt-Ranaei/vless";
+    let mut resolver = geoip::GeoResolver::open(cli.mmdb_path.as_deref());
+
+    if cli.daemon {
+        run_daemon(
+            &cli.subs_file,
+            output_dir,
+            repo_url,
+            cli.interval_hours,
+            &mut resolver,
+        )
+        .await
+    } else {
+        let mut last_hash = None;
+        if let Err(e) =
+            run_cycle(&cli.subs_file, output_dir, repo_url, &mut last_hash, &mut resolver).await
+        {
+            eprintln!("\n✗ {}", e);
+            std::process::exit(1);
+        }
+        Ok(())
     }
-    
-    let subs_file = &args[1];
-    
+}
+
+// Parsed command-line invocation: the subscriptions file plus the
+// optional daemon settings.
+struct CliArgs {
+    subs_file: String,
+    daemon: bool,
+    interval_hours: u64,
+    mmdb_path: Option<String>,
+}
+
+fn parse_args(args: &[String]) -> Result<CliArgs, String> {
+    let mut subs_file = None;
+    let mut daemon = false;
+    let mut interval_hours = 6u64;
+    let mut mmdb_path = None;
+
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--daemon" => daemon = true,
+            "--interval" => {
+                let value = iter.next().ok_or("--interval requires a value")?;
+                interval_hours = value
+                    .parse()
+                    .map_err(|_| "--interval must be a positive number of hours".to_string())?;
+                if interval_hours == 0 {
+                    return Err("--interval must be a positive number of hours".to_string());
+                }
+            }
+            "--mmdb" => {
+                let value = iter.next().ok_or("--mmdb requires a path")?;
+                mmdb_path = Some(value.clone());
+            }
+            other if subs_file.is_none() => subs_file = Some(other.to_string()),
+            other => return Err(format!("unexpected argument: {}", other)),
+        }
+    }
+
+    Ok(CliArgs {
+        subs_file: subs_file.ok_or_else(|| "missing <subscriptions_file> argument".to_string())?,
+        daemon,
+        interval_hours,
+        mmdb_path,
+    })
+}
+
+// Hash the sorted config set so a cycle can tell whether anything
+// actually changed before rewriting output files.
+fn compute_config_hash(configs: &[String]) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let mut sorted = configs.to_vec();
+    sorted.sort();
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    sorted.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn subs_file_mtime(path: &str) -> Option<std::time::SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+// Loop the fetch -> dedup -> categorize -> probe -> save pipeline on a
+// fixed interval, hot-reloading the subscriptions file on each cycle
+// (it's re-read from disk every time, so edits need no restart) and
+// logging when its modification time actually changes.
+async fn run_daemon(
+    subs_file: &str,
+    output_dir: &Path,
+    repo_url: &str,
+    interval_hours: u64,
+    resolver: &mut geoip::GeoResolver,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!(
+        "🕑 Starting daemon mode (interval = {}h, watching {})",
+        interval_hours, subs_file
+    );
+
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval_hours * 3600));
+    let mut last_hash = None;
+    let mut last_mtime = subs_file_mtime(subs_file);
+
+    loop {
+        ticker.tick().await;
+
+        let current_mtime = subs_file_mtime(subs_file);
+        if current_mtime != last_mtime {
+            println!("\n🔄 Subscriptions file changed, reloading this cycle");
+            last_mtime = current_mtime;
+        }
+
+        if let Err(e) = run_cycle(subs_file, output_dir, repo_url, &mut last_hash, resolver).await
+        {
+            eprintln!("\n✗ Cycle failed: {}", e);
+        }
+    }
+}
+
+// Run a single fetch -> dedup -> categorize -> probe -> save cycle.
+// The static output files are only rewritten when the sorted config
+// set's hash differs from the last cycle's, to avoid needless churn and
+// git noise; the alive and per-country files are rewritten every cycle.
+async fn run_cycle(
+    subs_file: &str,
+    output_dir: &Path,
+    repo_url: &str,
+    last_hash: &mut Option<u64>,
+    resolver: &mut geoip::GeoResolver,
+) -> Result<(), Box<dyn std::error::Error>> {
     let urls: Vec<String> = fs::read_to_string(subs_file)?
         .lines()
         .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
         .map(|s| s.to_string())
         .collect();
-    
+
     println!("📋 Found {} subscription URLs", urls.len());
-    
-    let output_dir = Path::new("configs");
-    fs::create_dir_all(output_dir)?;
-    
+
     let all_configs = fetch_all(urls).await;
-    
+
     if all_configs.is_empty() {
-        eprintln!("\n✗ No configs downloaded!");
-        std::process::exit(1);
+        return Err("No configs downloaded".into());
     }
-    
+
     let unique_configs = deduplicate(all_configs);
-    let categories = categorize(unique_configs.clone());
-    
-    save_all_configs(&unique_configs, output_dir)?;
-    save_by_transport(&categories, output_dir)?;
-    split_configs(&unique_configs, 300, output_dir)?;
-    
-    let repo_url = "https://github.com/Matt-Ranaei/vless";
-    update_readme(output_dir, repo_url)?;
-    
+
+    // Probing and GeoIP lookups are time-varying (server uptime, DNS,
+    // latency) independent of whether the URL set itself changed, so
+    // they always run on every cycle - unlike the static per-transport/
+    // security/split files below, skipping them would serve stale
+    // alive/country data forever once the subscriptions file settles.
+    let (alive_configs, dead_count, probe_malformed) = probe::probe_all(unique_configs.clone()).await;
+    let by_country = geoip::bucket_by_country(resolver, &unique_configs).await;
+    save_alive_configs(&alive_configs, output_dir)?;
+    save_by_country(&by_country, output_dir)?;
+
+    let hash = compute_config_hash(&unique_configs);
+    if *last_hash == Some(hash) {
+        println!("\n⏭  Config set unchanged since last cycle, skipping static file rewrites");
+    } else {
+        *last_hash = Some(hash);
+
+        let (by_transport, by_security, _malformed) = categorize(unique_configs.clone());
+
+        save_all_configs(&unique_configs, output_dir)?;
+        save_by_transport(&by_transport, output_dir)?;
+        save_by_security(&by_security, output_dir)?;
+        split_configs(&unique_configs, 300, output_dir)?;
+        output::write_client_configs(&unique_configs, output_dir)?;
+        update_readme(output_dir, repo_url)?;
+    }
+
     println!("\n{}", "=".repeat(60));
-    println!("✅ All done!");
+    println!("✅ Cycle complete!");
     println!("{}", "=".repeat(60));
     println!("📁 Output directory: configs/");
     println!("📊 Total unique configs: {}", unique_configs.len());
-    println!("📝 README.md updated with raw links");
+    println!(
+        "📡 Alive: {} | Dead: {} | Malformed: {}",
+        alive_configs.len(),
+        dead_count,
+        probe_malformed
+    );
     println!("{}", "=".repeat(60));
-    
+
     Ok(())
 }
@@ -0,0 +1,283 @@
+// Ready-to-use client config output: sing-box JSON outbounds and
+// Clash-style YAML proxies, built from the structured vless:// parser
+// instead of the raw subscription lines. Fields that don't apply to a
+// given config are simply omitted via `Option` + `skip_serializing_if`
+// rather than emitting something a client would choke on.
+
+use crate::parser::{SecurityMode, TransportType, VlessConfig};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+struct SingBoxReality {
+    enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    public_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct SingBoxUtls {
+    enabled: bool,
+    fingerprint: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SingBoxTls {
+    enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    server_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reality: Option<SingBoxReality>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    utls: Option<SingBoxUtls>,
+}
+
+#[derive(Debug, Serialize)]
+struct SingBoxTransport {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    headers: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    service_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct SingBoxOutbound {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    tag: String,
+    server: String,
+    server_port: u16,
+    uuid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    flow: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tls: Option<SingBoxTls>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transport: Option<SingBoxTransport>,
+}
+
+fn sing_box_tls(config: &VlessConfig) -> Option<SingBoxTls> {
+    if config.security == SecurityMode::None {
+        return None;
+    }
+
+    let reality = (config.security == SecurityMode::Reality).then(|| SingBoxReality {
+        enabled: true,
+        public_key: config.params.get("pbk").cloned(),
+        short_id: config.params.get("sid").cloned(),
+    });
+
+    let utls = config.fingerprint.clone().map(|fingerprint| SingBoxUtls {
+        enabled: true,
+        fingerprint,
+    });
+
+    Some(SingBoxTls {
+        enabled: true,
+        server_name: config.sni.clone(),
+        reality,
+        utls,
+    })
+}
+
+fn sing_box_transport(config: &VlessConfig) -> Option<SingBoxTransport> {
+    match config.transport {
+        TransportType::WebSocket => Some(SingBoxTransport {
+            kind: "ws",
+            path: config.params.get("path").cloned(),
+            headers: config
+                .params
+                .get("host")
+                .map(|host| HashMap::from([("Host".to_string(), host.clone())])),
+            service_name: None,
+        }),
+        TransportType::Grpc => Some(SingBoxTransport {
+            kind: "grpc",
+            path: None,
+            headers: None,
+            service_name: config.params.get("serviceName").cloned(),
+        }),
+        TransportType::XHttp => Some(SingBoxTransport {
+            kind: "httpupgrade",
+            path: config.params.get("path").cloned(),
+            headers: config
+                .params
+                .get("host")
+                .map(|host| HashMap::from([("Host".to_string(), host.clone())])),
+            service_name: None,
+        }),
+        TransportType::Tcp | TransportType::Tls => None,
+    }
+}
+
+fn to_sing_box_outbound(config: &VlessConfig, tag: String) -> SingBoxOutbound {
+    SingBoxOutbound {
+        kind: "vless",
+        tag,
+        server: config.address.clone(),
+        server_port: config.port,
+        uuid: config.uuid.clone(),
+        flow: config.flow.clone(),
+        tls: sing_box_tls(config),
+        transport: sing_box_transport(config),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ClashWsOpts {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    headers: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Serialize)]
+struct ClashGrpcOpts {
+    #[serde(rename = "grpc-service-name", skip_serializing_if = "Option::is_none")]
+    grpc_service_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ClashRealityOpts {
+    #[serde(rename = "public-key", skip_serializing_if = "Option::is_none")]
+    public_key: Option<String>,
+    #[serde(rename = "short-id", skip_serializing_if = "Option::is_none")]
+    short_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ClashProxy {
+    name: String,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    server: String,
+    port: u16,
+    uuid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    flow: Option<String>,
+    tls: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    servername: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    network: Option<String>,
+    #[serde(rename = "client-fingerprint", skip_serializing_if = "Option::is_none")]
+    client_fingerprint: Option<String>,
+    #[serde(rename = "ws-opts", skip_serializing_if = "Option::is_none")]
+    ws_opts: Option<ClashWsOpts>,
+    #[serde(rename = "grpc-opts", skip_serializing_if = "Option::is_none")]
+    grpc_opts: Option<ClashGrpcOpts>,
+    #[serde(rename = "reality-opts", skip_serializing_if = "Option::is_none")]
+    reality_opts: Option<ClashRealityOpts>,
+}
+
+fn to_clash_proxy(config: &VlessConfig, name: String) -> ClashProxy {
+    let network = match config.transport {
+        TransportType::WebSocket => Some("ws".to_string()),
+        TransportType::Grpc => Some("grpc".to_string()),
+        TransportType::XHttp => Some("http".to_string()),
+        TransportType::Tcp | TransportType::Tls => None,
+    };
+
+    let ws_opts = (config.transport == TransportType::WebSocket).then(|| ClashWsOpts {
+        path: config.params.get("path").cloned(),
+        headers: config
+            .params
+            .get("host")
+            .map(|host| HashMap::from([("Host".to_string(), host.clone())])),
+    });
+
+    let grpc_opts = (config.transport == TransportType::Grpc).then(|| ClashGrpcOpts {
+        grpc_service_name: config.params.get("serviceName").cloned(),
+    });
+
+    let reality_opts = (config.security == SecurityMode::Reality).then(|| ClashRealityOpts {
+        public_key: config.params.get("pbk").cloned(),
+        short_id: config.params.get("sid").cloned(),
+    });
+
+    ClashProxy {
+        name,
+        kind: "vless",
+        server: config.address.clone(),
+        port: config.port,
+        uuid: config.uuid.clone(),
+        flow: config.flow.clone(),
+        tls: config.security != SecurityMode::None,
+        servername: config.sni.clone(),
+        network,
+        client_fingerprint: config.fingerprint.clone(),
+        ws_opts,
+        grpc_opts,
+        reality_opts,
+    }
+}
+
+// Give every entry a readable, unique name: the decoded remark if
+// present, else `address:port`, de-duplicated with a numeric suffix.
+fn proxy_name(config: &VlessConfig, used_names: &mut HashMap<String, usize>) -> String {
+    let base = if config.remark.is_empty() {
+        format!("{}:{}", config.address, config.port)
+    } else {
+        config.remark.clone()
+    };
+
+    let count = used_names.entry(base.clone()).or_insert(0);
+    *count += 1;
+    if *count == 1 {
+        base
+    } else {
+        format!("{} ({})", base, count)
+    }
+}
+
+// Parse the raw config lines, serialize the valid ones into sing-box
+// and Clash formats, and write sing-box.json / clash.yaml alongside the
+// existing .txt outputs.
+pub fn write_client_configs(configs: &[String], output_dir: &Path) -> std::io::Result<()> {
+    println!("\n📦 Generating sing-box / Clash client configs...");
+
+    let parsed: Vec<VlessConfig> = configs.iter().filter_map(|c| VlessConfig::parse(c)).collect();
+
+    let mut used_names = HashMap::new();
+    let outbounds: Vec<SingBoxOutbound> = parsed
+        .iter()
+        .map(|c| to_sing_box_outbound(c, proxy_name(c, &mut used_names)))
+        .collect();
+
+    let sing_box_path = output_dir.join("sing-box.json");
+    let sing_box_json = serde_json::to_string_pretty(&SingBoxConfig { outbounds: &outbounds })?;
+    fs::write(&sing_box_path, sing_box_json)?;
+    println!("   ✓ sing-box.json ({} outbounds)", outbounds.len());
+
+    let mut used_names = HashMap::new();
+    let proxies: Vec<ClashProxy> = parsed
+        .iter()
+        .map(|c| to_clash_proxy(c, proxy_name(c, &mut used_names)))
+        .collect();
+
+    let clash_path = output_dir.join("clash.yaml");
+    let clash_yaml =
+        serde_yaml::to_string(&ClashConfig { proxies: &proxies }).map_err(std::io::Error::other)?;
+    fs::write(&clash_path, clash_yaml)?;
+    println!("   ✓ clash.yaml ({} proxies)", proxies.len());
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct SingBoxConfig<'a> {
+    outbounds: &'a [SingBoxOutbound],
+}
+
+#[derive(Debug, Serialize)]
+struct ClashConfig<'a> {
+    proxies: &'a [ClashProxy],
+}
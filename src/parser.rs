@@ -0,0 +1,196 @@
+// Structured parsing of vless:// URLs.
+//
+// Replaces the old substring-based `extract_transport`, which only ever
+// looked at `type=` in the query string and threw away everything else.
+// This decomposes `vless://<uuid>@<host>:<port>?<query>#<remark>` into a
+// typed struct built on the `url` crate, so malformed lines are rejected
+// up front instead of slipping through a `starts_with("vless://")` check.
+
+use std::collections::HashMap;
+use url::Url;
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum TransportType {
+    WebSocket,
+    Grpc,
+    Tcp,
+    Tls,
+    XHttp,
+}
+
+impl TransportType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::WebSocket => "ws",
+            Self::Grpc => "grpc",
+            Self::Tcp => "tcp",
+            Self::Tls => "tls",
+            Self::XHttp => "xhttp",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum SecurityMode {
+    None,
+    Tls,
+    Reality,
+}
+
+impl SecurityMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Tls => "tls",
+            Self::Reality => "reality",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VlessConfig {
+    pub raw: String,
+    pub uuid: String,
+    pub address: String,
+    pub port: u16,
+    pub params: HashMap<String, String>,
+    pub remark: String,
+    pub transport: TransportType,
+    pub security: SecurityMode,
+    pub flow: Option<String>,
+    pub sni: Option<String>,
+    pub fingerprint: Option<String>,
+}
+
+impl VlessConfig {
+    // Parse a single vless:// line. Returns None for anything malformed
+    // (missing uuid, host, or port) rather than letting it through.
+    pub fn parse(line: &str) -> Option<Self> {
+        let url = Url::parse(line).ok()?;
+        if url.scheme() != "vless" {
+            return None;
+        }
+
+        let uuid = url.username().to_string();
+        if uuid.is_empty() {
+            return None;
+        }
+        let address = url.host_str()?.to_string();
+        let port = url.port()?;
+
+        let params: HashMap<String, String> = url
+            .query_pairs()
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect();
+
+        let remark = url
+            .fragment()
+            .map(|f| {
+                percent_encoding::percent_decode_str(f)
+                    .decode_utf8_lossy()
+                    .into_owned()
+            })
+            .unwrap_or_default();
+
+        let security = match params.get("security").map(String::as_str) {
+            Some("tls") => SecurityMode::Tls,
+            Some("reality") => SecurityMode::Reality,
+            _ => SecurityMode::None,
+        };
+
+        let transport = match params.get("type").map(String::as_str) {
+            Some(t) if t.starts_with("ws") => TransportType::WebSocket,
+            Some(t) if t.starts_with("grpc") => TransportType::Grpc,
+            Some(t) if t.starts_with("xhttp") || t.starts_with("httpupgrade") => {
+                TransportType::XHttp
+            }
+            _ if security == SecurityMode::Tls => TransportType::Tls,
+            _ => TransportType::Tcp,
+        };
+
+        Some(VlessConfig {
+            raw: line.to_string(),
+            uuid,
+            address,
+            port,
+            flow: params.get("flow").cloned(),
+            // `sni` is absent on a lot of real-world ws/httpupgrade configs
+            // that rely on the Host header as SNI instead, so fall back to
+            // `host` rather than leaving TLS configs without a server name.
+            sni: params.get("sni").or_else(|| params.get("host")).cloned(),
+            fingerprint: params.get("fp").cloned(),
+            params,
+            remark,
+            transport,
+            security,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ws_tls_config() {
+        let config = VlessConfig::parse(
+            "vless://uuid-1@example.com:443?type=ws&security=tls&path=%2Fws&host=cdn.example.com#My%20Node",
+        )
+        .unwrap();
+
+        assert_eq!(config.uuid, "uuid-1");
+        assert_eq!(config.address, "example.com");
+        assert_eq!(config.port, 443);
+        assert_eq!(config.transport, TransportType::WebSocket);
+        assert_eq!(config.security, SecurityMode::Tls);
+        assert_eq!(config.remark, "My Node");
+    }
+
+    #[test]
+    fn falls_back_to_host_for_sni() {
+        let config = VlessConfig::parse(
+            "vless://uuid-1@example.com:443?type=ws&security=tls&host=cdn.example.com",
+        )
+        .unwrap();
+
+        assert_eq!(config.sni.as_deref(), Some("cdn.example.com"));
+    }
+
+    #[test]
+    fn prefers_explicit_sni_over_host() {
+        let config = VlessConfig::parse(
+            "vless://uuid-1@example.com:443?type=ws&security=tls&host=cdn.example.com&sni=real.example.com",
+        )
+        .unwrap();
+
+        assert_eq!(config.sni.as_deref(), Some("real.example.com"));
+    }
+
+    #[test]
+    fn rejects_non_vless_scheme() {
+        assert!(VlessConfig::parse("vmess://uuid-1@example.com:443").is_none());
+    }
+
+    #[test]
+    fn rejects_missing_uuid() {
+        assert!(VlessConfig::parse("vless://example.com:443").is_none());
+    }
+
+    #[test]
+    fn rejects_missing_port() {
+        assert!(VlessConfig::parse("vless://uuid-1@example.com").is_none());
+    }
+
+    #[test]
+    fn defaults_to_tcp_transport_without_tls() {
+        let config = VlessConfig::parse("vless://uuid-1@example.com:443").unwrap();
+        assert_eq!(config.transport, TransportType::Tcp);
+        assert_eq!(config.security, SecurityMode::None);
+    }
+
+    #[test]
+    fn tls_without_explicit_type_is_tls_transport() {
+        let config = VlessConfig::parse("vless://uuid-1@example.com:443?security=tls").unwrap();
+        assert_eq!(config.transport, TransportType::Tls);
+    }
+}
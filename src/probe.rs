@@ -0,0 +1,158 @@
+// Active liveness and latency probing for parsed vless:// configs.
+//
+// A config is only useful if the server behind it actually answers, so
+// this module opens a real TCP connection (and, for tls/reality configs,
+// completes a TLS handshake) to measure round-trip time and drop dead
+// entries before they land in the output files.
+
+use crate::parser::{SecurityMode, VlessConfig};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
+use tokio::time::timeout;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+const PROBE_CONCURRENCY: usize = 64;
+
+#[derive(Debug, Clone)]
+pub struct ProbeResult {
+    pub config: String,
+    pub latency_ms: u64,
+}
+
+// Reachability-only cert verifier: we're measuring whether a TLS
+// handshake completes, not whether the server's certificate should be
+// trusted, so every certificate is accepted.
+#[derive(Debug)]
+struct AcceptAllVerifier;
+
+impl tokio_rustls::rustls::client::danger::ServerCertVerifier for AcceptAllVerifier {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &tokio_rustls::rustls::pki_types::CertificateDer<'_>,
+        _intermediates: &[tokio_rustls::rustls::pki_types::CertificateDer<'_>],
+        _server_name: &tokio_rustls::rustls::pki_types::ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: tokio_rustls::rustls::pki_types::UnixTime,
+    ) -> Result<tokio_rustls::rustls::client::danger::ServerCertVerified, tokio_rustls::rustls::Error>
+    {
+        Ok(tokio_rustls::rustls::client::danger::ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &tokio_rustls::rustls::pki_types::CertificateDer<'_>,
+        _dss: &tokio_rustls::rustls::DigitallySignedStruct,
+    ) -> Result<tokio_rustls::rustls::client::danger::HandshakeSignatureValid, tokio_rustls::rustls::Error>
+    {
+        Ok(tokio_rustls::rustls::client::danger::HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &tokio_rustls::rustls::pki_types::CertificateDer<'_>,
+        _dss: &tokio_rustls::rustls::DigitallySignedStruct,
+    ) -> Result<tokio_rustls::rustls::client::danger::HandshakeSignatureValid, tokio_rustls::rustls::Error>
+    {
+        Ok(tokio_rustls::rustls::client::danger::HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<tokio_rustls::rustls::SignatureScheme> {
+        tokio_rustls::rustls::crypto::aws_lc_rs::default_provider()
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+async fn tls_handshake(stream: TcpStream, sni: &str) -> Option<()> {
+    use tokio_rustls::rustls::ClientConfig;
+    use tokio_rustls::TlsConnector;
+
+    let config = ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAllVerifier))
+        .with_no_client_auth();
+    let connector = TlsConnector::from(Arc::new(config));
+    let server_name = tokio_rustls::rustls::pki_types::ServerName::try_from(sni.to_string()).ok()?;
+    connector.connect(server_name, stream).await.ok()?;
+    Some(())
+}
+
+// Connect, optionally complete a TLS handshake, and return the elapsed
+// time in milliseconds if the config is alive.
+async fn probe_one(config: &VlessConfig) -> Option<u64> {
+    let start = Instant::now();
+    let stream = timeout(
+        PROBE_TIMEOUT,
+        TcpStream::connect((config.address.as_str(), config.port)),
+    )
+    .await
+    .ok()?
+    .ok()?;
+
+    if config.security != SecurityMode::None {
+        let sni = config.sni.as_deref().unwrap_or(&config.address);
+        timeout(PROBE_TIMEOUT, tls_handshake(stream, sni)).await.ok()??;
+    }
+
+    Some(start.elapsed().as_millis() as u64)
+}
+
+// Probe every config in parallel (bounded by PROBE_CONCURRENCY) and
+// return the alive ones sorted ascending by latency, the dead count,
+// and a separate malformed count for lines `VlessConfig::parse` rejects
+// outright - those never get a connection attempt, so they shouldn't be
+// counted as "dead" servers.
+pub async fn probe_all(configs: Vec<String>) -> (Vec<ProbeResult>, usize, usize) {
+    println!("\n{}", "=".repeat(60));
+    println!("🔎 Probing liveness and latency...");
+    println!("{}", "=".repeat(60));
+
+    let mut malformed_count = 0usize;
+    let parsed: Vec<VlessConfig> = configs
+        .into_iter()
+        .filter_map(|config| {
+            let parsed = VlessConfig::parse(&config);
+            if parsed.is_none() {
+                malformed_count += 1;
+            }
+            parsed
+        })
+        .collect();
+
+    let semaphore = Arc::new(Semaphore::new(PROBE_CONCURRENCY));
+    let tasks: Vec<_> = parsed
+        .into_iter()
+        .map(|config| {
+            let semaphore = Arc::clone(&semaphore);
+            tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await.unwrap();
+                let latency = probe_one(&config).await;
+                (config.raw, latency)
+            })
+        })
+        .collect();
+
+    let mut alive = Vec::new();
+    let mut dead_count = 0usize;
+    for task in tasks {
+        if let Ok((config, latency)) = task.await {
+            match latency {
+                Some(latency_ms) => alive.push(ProbeResult { config, latency_ms }),
+                None => dead_count += 1,
+            }
+        }
+    }
+
+    alive.sort_by_key(|r| r.latency_ms);
+    println!(
+        "\n✓ Alive: {} | ✗ Dead: {} | ⚠ Malformed: {}",
+        alive.len(),
+        dead_count,
+        malformed_count
+    );
+    (alive, dead_count, malformed_count)
+}